@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use log::{debug, error, info};
 use serde::{
@@ -7,23 +7,20 @@ use serde::{
 
 use crate::{
     ifaces::inter_ifaces_controller::{
-        handle_changed_ports, set_ifaces_up_priority,
+        commit_ifaces_async, handle_changed_ports, retrieve_current_async,
     },
     ErrorKind, Interface, InterfaceState, InterfaceType, NmstateError,
 };
 
-// The max loop count for Interfaces.set_up_priority()
-// This allows interface with 4 nested levels in any order.
-// To support more nested level, user could place top controller at the
-// beginning of desire state
-const INTERFACES_SET_PRIORITY_MAX_RETRY: u32 = 4;
-
 #[derive(Clone, Debug, Default)]
 pub struct Interfaces {
     pub(crate) kernel_ifaces: HashMap<String, Interface>,
     pub(crate) user_ifaces: HashMap<(String, InterfaceType), Interface>,
-    // The insert_order is allowing user to provided ordered interface
-    // to support 5+ nested dependency.
+    // The insert_order is preserving the order interfaces were provided in.
+    // set_up_priority() seeds its topological sort with this order so that
+    // interfaces with no controller (and therefore no dependency to sort
+    // by) keep the order the user originally gave them in, instead of
+    // being re-alphabetized.
     pub(crate) insert_order: Vec<(String, InterfaceType)>,
 }
 
@@ -78,6 +75,17 @@ impl Interfaces {
         ifaces
     }
 
+    /// Returns a compact, `Serialize`-friendly view of every interface,
+    /// one entry per interface in `up_priority` order, for callers that
+    /// want a quick summary instead of the full per-interface detail.
+    pub fn to_brief(&self) -> Vec<IfaceBrief> {
+        self.to_vec()
+            .iter()
+            .enumerate()
+            .map(|(index, iface)| IfaceBrief::new(index, iface))
+            .collect()
+    }
+
     pub(crate) fn get_iface<'a, 'b>(
         &'a self,
         iface_name: &'b str,
@@ -180,13 +188,33 @@ impl Interfaces {
         &mut self,
         current: &Self,
     ) -> Result<(Self, Self, Self), NmstateError> {
-        let mut add_ifaces = Self::new();
-        let mut chg_ifaces = Self::new();
-        let mut del_ifaces = Self::new();
+        self.prepare_for_apply(current)?;
+        self.split_for_apply(current)
+    }
 
+    // The resolve -> changed-ports -> priority stages of the synchronous
+    // apply pipeline. apply_async_impl() runs the same stages itself, with
+    // an await point after each one.
+    fn prepare_for_apply(
+        &mut self,
+        current: &Self,
+    ) -> Result<(), NmstateError> {
         resolve_unknown_ifaces(self, current)?;
         handle_changed_ports(self, current)?;
         self.set_up_priority()?;
+        Ok(())
+    }
+
+    // Splits `self` into the (add, chg, del) triple expected by the
+    // backend, once the resolve -> changed-ports -> priority stages have
+    // already run.
+    fn split_for_apply(
+        &self,
+        current: &Self,
+    ) -> Result<(Self, Self, Self), NmstateError> {
+        let mut add_ifaces = Self::new();
+        let mut chg_ifaces = Self::new();
+        let mut del_ifaces = Self::new();
 
         for iface in self.to_vec() {
             if iface.is_absent() {
@@ -223,24 +251,183 @@ impl Interfaces {
         Ok((add_ifaces, chg_ifaces, del_ifaces))
     }
 
+    /// Async counterpart of the synchronous apply pipeline built on top of
+    /// [`Interfaces::gen_state_for_apply`], for callers integrating nmstate
+    /// into an event-driven daemon that cannot block a thread while the
+    /// add/change/delete phases are committed to the backend. `.await`
+    /// points sit between the resolve, changed-ports, priority and commit
+    /// stages so the executor can interleave other work.
+    pub async fn apply_async(
+        &mut self,
+        current: &Self,
+    ) -> Result<(), NmstateError> {
+        self.apply_async_impl(current, true).await
+    }
+
+    /// Same as [`Interfaces::apply_async`], but returns as soon as the
+    /// backend has accepted the change, without running
+    /// [`Interfaces::verify`].
+    pub async fn apply_async_no_verify(
+        &mut self,
+        current: &Self,
+    ) -> Result<(), NmstateError> {
+        self.apply_async_impl(current, false).await
+    }
+
+    async fn apply_async_impl(
+        &mut self,
+        current: &Self,
+        run_verify: bool,
+    ) -> Result<(), NmstateError> {
+        resolve_unknown_ifaces(self, current)?;
+        tokio::task::yield_now().await;
+
+        handle_changed_ports(self, current)?;
+        tokio::task::yield_now().await;
+
+        self.set_up_priority()?;
+        tokio::task::yield_now().await;
+
+        let (add_ifaces, chg_ifaces, del_ifaces) =
+            self.split_for_apply(current)?;
+
+        commit_ifaces_async(&add_ifaces, &chg_ifaces, &del_ifaces).await?;
+
+        if run_verify {
+            // `current` is the pre-commit snapshot: interfaces in
+            // `add_ifaces` are guaranteed absent from it, so verifying
+            // against it would always fail with "Failed to find desired
+            // interface". Re-query live state after the commit instead.
+            let post_commit_current = retrieve_current_async().await?;
+            self.verify(&post_commit_current)?;
+        }
+
+        Ok(())
+    }
+
+    // Assigns `up_priority` via a topological sort (Kahn's algorithm) over
+    // the controller -> port dependency graph, so controllers always get a
+    // lower up_priority than their ports regardless of nesting depth.
     pub fn set_up_priority(&mut self) -> Result<(), NmstateError> {
-        for _ in 0..INTERFACES_SET_PRIORITY_MAX_RETRY {
-            if set_ifaces_up_priority(self) {
-                return Ok(());
+        let ifaces = self.to_vec();
+
+        // Name -> graph key index, built once, so resolving a controller's
+        // port list is O(1) per port instead of a linear scan over every
+        // interface. This is what keeps the whole pass at O(V+E).
+        let key_by_name: HashMap<&str, (String, InterfaceType)> = ifaces
+            .iter()
+            .map(|iface| {
+                (iface.name(), (iface.name().to_string(), iface.iface_type()))
+            })
+            .collect();
+
+        let mut ports_of: HashMap<(String, InterfaceType), Vec<(String, InterfaceType)>> =
+            HashMap::new();
+        for iface in &ifaces {
+            let ctrl_key = (iface.name().to_string(), iface.iface_type());
+            if let Some(port_names) = iface.ports() {
+                for port_name in port_names {
+                    if let Some(port_key) = key_by_name.get(port_name) {
+                        ports_of
+                            .entry(ctrl_key.clone())
+                            .or_default()
+                            .push(port_key.clone());
+                    }
+                }
             }
         }
-        error!(
-            "Failed to set up priority: please order the interfaces in desire \
-            state to place controller before its ports"
-        );
-        Err(NmstateError::new(
-            ErrorKind::InvalidArgument,
-            "Failed to set up priority: nmstate only support nested interface \
-            up to 4 levels. To support more nest level, \
-            please order the interfaces in desire \
-            state to place controller before its ports"
-                .to_string(),
-        ))
+
+        // Seed the sort in insert_order (the order interfaces were
+        // originally provided in), falling back to whatever is left in
+        // key_by_name for anything insert_order doesn't cover, so
+        // top-level interfaces with no controller keep their natural
+        // input order instead of being re-alphabetized by to_vec().
+        let mut seen = std::collections::HashSet::new();
+        let mut natural_order: Vec<(String, InterfaceType)> = Vec::new();
+        for key in self.insert_order.iter().chain(key_by_name.values()) {
+            if seen.insert(key.clone()) {
+                natural_order.push(key.clone());
+            }
+        }
+
+        match kahn_sort(&natural_order, &ports_of) {
+            Ok(sorted) => {
+                for (up_priority, key) in sorted.into_iter().enumerate() {
+                    if let Some(iface) = self.get_iface_mut(&key.0, key.1) {
+                        iface.base_iface_mut().up_priority = up_priority as u32;
+                    }
+                }
+                Ok(())
+            }
+            Err(loop_keys) => {
+                let loop_ifaces: Vec<String> = loop_keys
+                    .iter()
+                    .map(|(name, iface_type)| format!("{name}/{iface_type}"))
+                    .collect();
+                let e = NmstateError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Failed to set up priority: dependency loop found \
+                        among interfaces: {}",
+                        loop_ifaces.join(", ")
+                    ),
+                );
+                error!("{}", e);
+                Err(e)
+            }
+        }
+    }
+}
+
+// Runs Kahn's algorithm over a pre-extracted controller -> port edge map.
+// `order` gives the node set and the order used both to seed the queue
+// and to break ties among simultaneously-ready nodes (so callers control
+// what "natural order" means). On success, returns every node from
+// `order` in topological (controller-before-port) order. On a dependency
+// cycle, returns the sub-list of nodes that never reached zero in-degree.
+fn kahn_sort(
+    order: &[(String, InterfaceType)],
+    ports_of: &HashMap<(String, InterfaceType), Vec<(String, InterfaceType)>>,
+) -> Result<Vec<(String, InterfaceType)>, Vec<(String, InterfaceType)>> {
+    let mut in_degree: HashMap<(String, InterfaceType), u32> =
+        order.iter().cloned().map(|key| (key, 0)).collect();
+    for port_keys in ports_of.values() {
+        for port_key in port_keys {
+            *in_degree.entry(port_key.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let mut queue: VecDeque<(String, InterfaceType)> = order
+        .iter()
+        .filter(|key| in_degree.get(*key) == Some(&0))
+        .cloned()
+        .collect();
+
+    let mut sorted = Vec::new();
+    while let Some(key) = queue.pop_front() {
+        sorted.push(key.clone());
+        if let Some(port_keys) = ports_of.get(&key) {
+            for port_key in port_keys {
+                if let Some(degree) = in_degree.get_mut(port_key) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        queue.push_back(port_key.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    if sorted.len() == order.len() {
+        Ok(sorted)
+    } else {
+        let sorted_set: std::collections::HashSet<_> =
+            sorted.iter().cloned().collect();
+        Err(order
+            .iter()
+            .filter(|key| !sorted_set.contains(*key))
+            .cloned()
+            .collect())
     }
 }
 
@@ -375,4 +562,226 @@ fn resolve_unknown_ifaces(
         ifaces.push(new_iface);
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Compact "what's on this box" view of a single interface, as produced by
+/// [`Interfaces::to_brief()`]. Unlike [`Interface`], this only carries the
+/// handful of fields users look for at a glance instead of the full
+/// per-interface desired/current state.
+#[derive(Clone, Debug, Serialize)]
+pub struct IfaceBrief {
+    // Position of this interface in Interfaces::to_vec() (i.e. up_priority)
+    // order, so JSON/YAML consumers get the ordering explicitly instead of
+    // having to infer it from array position.
+    pub index: usize,
+    pub name: String,
+    pub iface_type: InterfaceType,
+    pub state: InterfaceState,
+    pub mtu: Option<u64>,
+    pub mac_address: Option<String>,
+    pub ipv4: Vec<IfaceBriefIpAddr>,
+    pub ipv6: Vec<IfaceBriefIpAddr>,
+}
+
+impl IfaceBrief {
+    fn new(index: usize, iface: &Interface) -> Self {
+        let base_iface = iface.base_iface();
+        Self {
+            index,
+            name: iface.name().to_string(),
+            iface_type: iface.iface_type(),
+            state: base_iface.state,
+            mtu: base_iface.mtu,
+            mac_address: base_iface.mac_address.clone(),
+            ipv4: IfaceBriefIpAddr::from_ipv4(base_iface.ipv4.as_ref()),
+            ipv6: IfaceBriefIpAddr::from_ipv6(base_iface.ipv6.as_ref()),
+        }
+    }
+}
+
+/// A single assigned address as shown by [`IfaceBrief`].
+#[derive(Clone, Debug, Serialize)]
+pub struct IfaceBriefIpAddr {
+    pub ip: String,
+    pub prefix_length: u8,
+    pub gateway: Option<String>,
+}
+
+impl IfaceBriefIpAddr {
+    fn from_ipv4(ipv4: Option<&crate::InterfaceIpv4>) -> Vec<Self> {
+        if let Some(ipv4) = ipv4 {
+            let gateway = ipv4.gateway.clone();
+            ipv4.addresses
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|addr| Self {
+                    ip: addr.ip.to_string(),
+                    prefix_length: addr.prefix_length,
+                    gateway: gateway.clone(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn from_ipv6(ipv6: Option<&crate::InterfaceIpv6>) -> Vec<Self> {
+        if let Some(ipv6) = ipv6 {
+            let gateway = ipv6.gateway.clone();
+            ipv6.addresses
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|addr| Self {
+                    ip: addr.ip.to_string(),
+                    prefix_length: addr.prefix_length,
+                    gateway: gateway.clone(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> (String, InterfaceType) {
+        (name.to_string(), InterfaceType::Unknown)
+    }
+
+    #[test]
+    fn kahn_sort_detects_cycle() {
+        let order = vec![key("eth1"), key("eth2")];
+        let mut ports_of = HashMap::new();
+        // eth1 -> eth2 -> eth1: neither ever reaches in-degree zero.
+        ports_of.insert(key("eth1"), vec![key("eth2")]);
+        ports_of.insert(key("eth2"), vec![key("eth1")]);
+
+        let mut err = kahn_sort(&order, &ports_of).unwrap_err();
+        err.sort();
+        let mut expected = vec![key("eth1"), key("eth2")];
+        expected.sort();
+        assert_eq!(err, expected);
+    }
+
+    #[test]
+    fn kahn_sort_detects_self_referencing_port() {
+        let order = vec![key("bond0")];
+        let mut ports_of = HashMap::new();
+        ports_of.insert(key("bond0"), vec![key("bond0")]);
+
+        let err = kahn_sort(&order, &ports_of).unwrap_err();
+        assert_eq!(err, vec![key("bond0")]);
+    }
+
+    #[test]
+    fn kahn_sort_orders_deeply_nested_controllers_before_ports() {
+        // br0 -> bond0 -> vlan0 -> vlan1 -> macvlan0: 5 levels, one more
+        // than the old fixed 4-level retry loop supported.
+        let order = vec![
+            key("br0"),
+            key("bond0"),
+            key("vlan0"),
+            key("vlan1"),
+            key("macvlan0"),
+        ];
+        let mut ports_of = HashMap::new();
+        ports_of.insert(key("br0"), vec![key("bond0")]);
+        ports_of.insert(key("bond0"), vec![key("vlan0")]);
+        ports_of.insert(key("vlan0"), vec![key("vlan1")]);
+        ports_of.insert(key("vlan1"), vec![key("macvlan0")]);
+
+        let sorted = kahn_sort(&order, &ports_of).unwrap();
+        let pos = |name: &str| {
+            sorted.iter().position(|k| k == &key(name)).unwrap()
+        };
+        assert!(pos("br0") < pos("bond0"));
+        assert!(pos("bond0") < pos("vlan0"));
+        assert!(pos("vlan0") < pos("vlan1"));
+        assert!(pos("vlan1") < pos("macvlan0"));
+    }
+
+    #[test]
+    fn kahn_sort_keeps_natural_order_among_unrelated_interfaces() {
+        // None of these depend on each other, so they should come out in
+        // the exact order they were given in, not re-alphabetized.
+        let order = vec![key("zeta"), key("alpha"), key("mid")];
+        let ports_of = HashMap::new();
+
+        let sorted = kahn_sort(&order, &ports_of).unwrap();
+        assert_eq!(sorted, order);
+    }
+
+    #[test]
+    fn to_brief_reports_index_order_and_addresses() {
+        let mut ifaces = Interfaces::new();
+
+        let eth1: Interface = serde_json::from_value(serde_json::json!({
+            "name": "eth1",
+            "type": "ethernet",
+            "state": "up",
+            "mtu": 1500,
+            "mac-address": "00:11:22:33:44:55",
+            "ipv4": {
+                "enabled": true,
+                "gateway": "192.0.2.254",
+                "address": [
+                    {"ip": "192.0.2.1", "prefix-length": 24}
+                ]
+            }
+        }))
+        .unwrap();
+        ifaces.push(eth1);
+
+        let eth2: Interface = serde_json::from_value(serde_json::json!({
+            "name": "eth2",
+            "type": "ethernet",
+            "state": "down",
+            "ipv6": {
+                "enabled": true,
+                "gateway": "2001:db8::1",
+                "address": [
+                    {"ip": "2001:db8::2", "prefix-length": 64}
+                ]
+            }
+        }))
+        .unwrap();
+        ifaces.push(eth2);
+
+        let brief = ifaces.to_brief();
+        assert_eq!(brief.len(), 2);
+
+        // eth1 sorts before eth2 (both share the default up_priority), so
+        // it should land at index 0.
+        assert_eq!(brief[0].index, 0);
+        assert_eq!(brief[0].name, "eth1");
+        assert_eq!(brief[0].mtu, Some(1500));
+        assert_eq!(
+            brief[0].mac_address.as_deref(),
+            Some("00:11:22:33:44:55")
+        );
+        assert_eq!(brief[0].ipv4.len(), 1);
+        assert_eq!(brief[0].ipv4[0].ip, "192.0.2.1");
+        assert_eq!(brief[0].ipv4[0].prefix_length, 24);
+        assert_eq!(
+            brief[0].ipv4[0].gateway.as_deref(),
+            Some("192.0.2.254")
+        );
+        assert!(brief[0].ipv6.is_empty());
+
+        assert_eq!(brief[1].index, 1);
+        assert_eq!(brief[1].name, "eth2");
+        assert!(brief[1].ipv4.is_empty());
+        assert_eq!(brief[1].ipv6.len(), 1);
+        assert_eq!(brief[1].ipv6[0].ip, "2001:db8::2");
+        assert_eq!(brief[1].ipv6[0].prefix_length, 64);
+        assert_eq!(
+            brief[1].ipv6[0].gateway.as_deref(),
+            Some("2001:db8::1")
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use log::info;
+use tokio::task;
+
+use crate::{ErrorKind, InterfaceType, Interfaces, NmstateError};
+
+/// Releases ports that a controller no longer lists in its desired port
+/// list, so kernel state can catch up with the new controller/port
+/// relationship instead of leaving stale ports attached.
+pub(crate) fn handle_changed_ports(
+    ifaces: &mut Interfaces,
+    current: &Interfaces,
+) -> Result<(), NmstateError> {
+    let mut released_ports = Vec::new();
+
+    for iface in ifaces.to_vec() {
+        if let Some(desired_ports) = iface.ports() {
+            if let Some(cur_iface) =
+                current.get_iface(iface.name(), iface.iface_type())
+            {
+                if let Some(cur_ports) = cur_iface.ports() {
+                    for cur_port_name in cur_ports {
+                        if desired_ports.contains(&cur_port_name) {
+                            continue;
+                        }
+                        if ifaces
+                            .get_iface(
+                                cur_port_name,
+                                InterfaceType::Unknown,
+                            )
+                            .is_some()
+                        {
+                            // User already has explicit desired state for
+                            // this interface elsewhere; do not override
+                            // it here.
+                            continue;
+                        }
+                        if let Some(cur_port_iface) = current.get_iface(
+                            cur_port_name,
+                            InterfaceType::Unknown,
+                        ) {
+                            info!(
+                                "Releasing port {} as it is no longer \
+                                listed by controller {}",
+                                cur_port_name,
+                                iface.name()
+                            );
+                            let mut released = cur_port_iface.clone();
+                            released.base_iface_mut().controller = None;
+                            released_ports.push(released);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for released in released_ports {
+        ifaces.push(released);
+    }
+
+    Ok(())
+}
+
+/// Commits the add/chg/del interface sets produced by
+/// [`Interfaces::gen_state_for_apply`] to the kernel (nispor) backend, for
+/// callers driving the async apply pipeline
+/// ([`Interfaces::apply_async`]/[`Interfaces::apply_async_no_verify`]).
+/// Sets are committed in add, then chg, then del order, matching the
+/// order of the `(add, chg, del)` triple `split_for_apply()` returns. Interface types backed by a
+/// different plugin (OVS db, dispatch scripts, ...) are out of scope here
+/// and would need their own stage alongside this one.
+/// Each non-empty set is handed to
+/// [`nispor_apply`](crate::nispor::nispor_apply) inside `spawn_blocking`,
+/// since the underlying netlink calls block the calling thread and must
+/// not stall the caller's async executor.
+pub(crate) async fn commit_ifaces_async(
+    add_ifaces: &Interfaces,
+    chg_ifaces: &Interfaces,
+    del_ifaces: &Interfaces,
+) -> Result<(), NmstateError> {
+    commit_ifaces_async_with(add_ifaces, chg_ifaces, del_ifaces, |ifaces| {
+        crate::nispor::nispor_apply(ifaces)
+    })
+    .await
+}
+
+/// Re-queries live kernel state after a commit, so callers can verify
+/// against what actually landed in the kernel instead of the pre-commit
+/// snapshot.
+pub(crate) async fn retrieve_current_async() -> Result<Interfaces, NmstateError>
+{
+    task::spawn_blocking(crate::nispor::nispor_retrieve)
+        .await
+        .map_err(|e| {
+            NmstateError::new(
+                ErrorKind::Bug,
+                format!("Backend retrieve task panicked: {e}"),
+            )
+        })?
+}
+
+async fn commit_ifaces_async_with<F>(
+    add_ifaces: &Interfaces,
+    chg_ifaces: &Interfaces,
+    del_ifaces: &Interfaces,
+    commit_fn: F,
+) -> Result<(), NmstateError>
+where
+    F: Fn(&Interfaces) -> Result<(), NmstateError> + Clone + Send + 'static,
+{
+    commit_iface_set_async(add_ifaces, commit_fn.clone()).await?;
+    commit_iface_set_async(chg_ifaces, commit_fn.clone()).await?;
+    commit_iface_set_async(del_ifaces, commit_fn).await?;
+    Ok(())
+}
+
+async fn commit_iface_set_async<F>(
+    ifaces: &Interfaces,
+    commit_fn: F,
+) -> Result<(), NmstateError>
+where
+    F: Fn(&Interfaces) -> Result<(), NmstateError> + Send + 'static,
+{
+    if ifaces.to_vec().is_empty() {
+        return Ok(());
+    }
+
+    let ifaces = ifaces.clone();
+    task::spawn_blocking(move || commit_fn(&ifaces))
+        .await
+        .map_err(|e| {
+            NmstateError::new(
+                ErrorKind::Bug,
+                format!("Backend commit task panicked: {e}"),
+            )
+        })?
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn iface_named(name: &str) -> crate::Interface {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "type": "ethernet",
+            "state": "up",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn commit_ifaces_async_commits_add_then_chg_then_del() {
+        let mut add_ifaces = Interfaces::new();
+        add_ifaces.push(iface_named("add0"));
+        let mut chg_ifaces = Interfaces::new();
+        chg_ifaces.push(iface_named("chg0"));
+        let mut del_ifaces = Interfaces::new();
+        del_ifaces.push(iface_named("del0"));
+
+        let log: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let log_clone = log.clone();
+        commit_ifaces_async_with(
+            &add_ifaces,
+            &chg_ifaces,
+            &del_ifaces,
+            move |ifaces| {
+                for iface in ifaces.to_vec() {
+                    log_clone.lock().unwrap().push(iface.name().to_string());
+                }
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "add0".to_string(),
+                "chg0".to_string(),
+                "del0".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn commit_ifaces_async_skips_empty_sets() {
+        let empty = Interfaces::new();
+        let called = Arc::new(Mutex::new(false));
+        let called_clone = called.clone();
+
+        commit_ifaces_async_with(&empty, &empty, &empty, move |_| {
+            *called_clone.lock().unwrap() = true;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        assert!(!*called.lock().unwrap());
+    }
+}